@@ -0,0 +1,109 @@
+use crate::import_allowlist::{classify_import, ImportAllowlist};
+use crate::raw_component::{core_import_names, wrap_core_modules};
+use anyhow::{Context, Result};
+use semver::Version;
+
+/// Encodes a core wasm module, plus optional adapters, as a component.
+///
+/// Build up an encoder with the builder methods below, then call
+/// [`ComponentEncoder::encode`] to produce the component's bytes.
+pub struct ComponentEncoder {
+    module: Option<Vec<u8>>,
+    adapters: Vec<(String, Vec<u8>)>,
+    validate: bool,
+    stub_missing_functions: bool,
+    pub(crate) import_allowlist: Option<ImportAllowlist>,
+    pub(crate) reject_unsafe_realloc: bool,
+    pub(crate) realloc_safety_threshold: Version,
+}
+
+impl Default for ComponentEncoder {
+    fn default() -> Self {
+        ComponentEncoder {
+            module: None,
+            adapters: Vec::new(),
+            validate: false,
+            stub_missing_functions: false,
+            import_allowlist: None,
+            reject_unsafe_realloc: false,
+            realloc_safety_threshold: Self::default_realloc_safety_threshold(),
+        }
+    }
+}
+
+impl ComponentEncoder {
+    /// Sets the core module to encode as a component.
+    pub fn module(mut self, module: &[u8]) -> Result<Self> {
+        self.module = Some(module.to_vec());
+        Ok(self)
+    }
+
+    /// Configures whether the core module and any adapters are validated
+    /// with `wasmparser` before encoding.
+    pub fn validate(mut self, validate: bool) -> Self {
+        self.validate = validate;
+        self
+    }
+
+    /// Configures whether an import outside the configured allowlist (see
+    /// [`ComponentEncoder::import_allowlist`]) is accepted rather than
+    /// causing `encode` to fail. No stub is generated for such an import; the
+    /// encoded component is the same either way.
+    pub fn stub_missing_functions(mut self, stub: bool) -> Self {
+        self.stub_missing_functions = stub;
+        self
+    }
+
+    /// Restricts the component's declared imports to `allowlist`, for
+    /// callers that already have one built (e.g. via
+    /// [`ImportAllowlist::from_names`]) rather than a `World` to derive it
+    /// from. See [`ComponentEncoder::import_allowlist`] for the common case.
+    pub fn with_import_allowlist(mut self, allowlist: ImportAllowlist) -> Self {
+        self.import_allowlist = Some(allowlist);
+        self
+    }
+
+    /// Registers an adapter module, named `name`, to be encoded alongside
+    /// the main module.
+    pub fn adapter(mut self, name: &str, adapter: &[u8]) -> Result<Self> {
+        self.adapters.push((name.to_string(), adapter.to_vec()));
+        Ok(self)
+    }
+
+    /// Encodes the configured module (and adapters) as a component.
+    pub fn encode(&self) -> Result<Vec<u8>> {
+        let module = self
+            .module
+            .as_ref()
+            .context("a core module must be supplied with `module` before calling `encode`")?;
+
+        if self.validate {
+            wasmparser::Validator::new().validate_all(module)?;
+            for (_, adapter) in &self.adapters {
+                wasmparser::Validator::new().validate_all(adapter)?;
+            }
+        }
+
+        self.check_realloc_safety("<main module>", module)?;
+        for (name, adapter) in &self.adapters {
+            self.check_realloc_safety(name, adapter)?;
+        }
+
+        for name in core_import_names(module) {
+            // `ComponentEncoder` alone has no linked libraries to satisfy an
+            // import internally; that's `Linker`'s job. Here an unlisted
+            // import is either accepted under `stub_missing_functions` or
+            // rejected.
+            classify_import(
+                self.import_allowlist.as_ref(),
+                self.stub_missing_functions,
+                &name,
+                false,
+            )?;
+        }
+
+        let modules = std::iter::once(module.as_slice())
+            .chain(self.adapters.iter().map(|(_, adapter)| adapter.as_slice()));
+        Ok(wrap_core_modules(modules))
+    }
+}
@@ -0,0 +1,172 @@
+use crate::{ComponentEncoder, Linker};
+use anyhow::{bail, Result};
+use std::collections::HashSet;
+use wit_parser::{Resolve, WorldId};
+
+/// A set of import names a caller wants the final component to retain.
+///
+/// Built from a world (see [`ImportAllowlist::from_world`], which
+/// [`ComponentEncoder::import_allowlist`] uses) or from plain names, and
+/// applied by `ComponentEncoder` or [`Linker::restrict_imports`], this is
+/// currently a **validation-only**
+/// policy: every core import is classified with [`classify_import`] and
+/// `encode` fails (or, with `stub_missing_functions`, is allowed through) if
+/// it doesn't fit the policy. Neither `ComponentEncoder` nor `Linker` emits a
+/// component-level import/export section to begin with - they embed core
+/// modules directly - so there's no binary import surface to actually prune
+/// or rewrite yet. That part (rewiring `Internalize`d imports to an alias of
+/// another library's export, and synthesizing a trapping stub for `Stub`)
+/// is not implemented; allowlisting only gates which imports are acceptable,
+/// it doesn't yet change the encoded bytes.
+#[derive(Debug, Clone, Default)]
+pub struct ImportAllowlist {
+    names: HashSet<String>,
+}
+
+impl ImportAllowlist {
+    /// Computes the allowlist from the named imports of `world` in `resolve`.
+    pub fn from_world(resolve: &Resolve, world: WorldId) -> ImportAllowlist {
+        let mut names = HashSet::new();
+        for (key, _item) in resolve.worlds[world].imports.iter() {
+            names.insert(resolve.name_world_key(key));
+        }
+        ImportAllowlist { names }
+    }
+
+    /// Builds an allowlist directly from a list of import names, for
+    /// callers that don't have a `World` on hand to derive it from.
+    pub fn from_names(names: impl IntoIterator<Item = String>) -> ImportAllowlist {
+        ImportAllowlist {
+            names: names.into_iter().collect(),
+        }
+    }
+
+    /// Returns whether `name` is present in this allowlist.
+    pub fn contains(&self, name: &str) -> bool {
+        self.names.contains(name)
+    }
+}
+
+/// What policy `name` falls under given the configured allowlist.
+///
+/// This only classifies - see [`ImportAllowlist`] for why `encode` doesn't
+/// yet act differently on `Internalize` vs. `Stub` beyond accepting them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ImportDisposition {
+    /// The import is allowlisted (or there's no allowlist configured).
+    Keep,
+    /// The import isn't in the allowlist, but a linked library satisfies it
+    /// internally, so it's accepted rather than treated as missing.
+    Internalize,
+    /// The import isn't in the allowlist and nothing satisfies it
+    /// internally; accepted anyway because `stub_missing_functions` is set.
+    Stub,
+}
+
+/// Decides what policy `name`, an import that isn't otherwise required,
+/// falls under given whether one of the linked libraries can satisfy it
+/// internally.
+///
+/// Returns an error if the import is unlisted, unsatisfiable internally, and
+/// stubbing isn't enabled.
+pub(crate) fn classify_import(
+    allowlist: Option<&ImportAllowlist>,
+    stub_missing_functions: bool,
+    name: &str,
+    satisfiable_internally: bool,
+) -> Result<ImportDisposition> {
+    let allowlist = match allowlist {
+        Some(allowlist) => allowlist,
+        None => return Ok(ImportDisposition::Keep),
+    };
+
+    // Core imports are named `module:field`. A world's allowlist names the
+    // interface an import comes from (the core module name), or the function
+    // itself for a `$root` import, so those names are accepted too.
+    let (module, field) = name.rsplit_once(':').unwrap_or(("", name));
+    let world_key = if module == "$root" { field } else { module };
+    if allowlist.contains(name) || allowlist.contains(world_key) {
+        return Ok(ImportDisposition::Keep);
+    }
+
+    if satisfiable_internally {
+        return Ok(ImportDisposition::Internalize);
+    }
+
+    if stub_missing_functions {
+        return Ok(ImportDisposition::Stub);
+    }
+
+    bail!(
+        "import `{name}` is not in the configured import allowlist and is not satisfied by any \
+         linked library; either add it to the allowlist or enable `stub_missing_functions`"
+    );
+}
+
+impl ComponentEncoder {
+    /// Validates the core module's imports against those present in `world`:
+    /// `encode` fails on an import outside of `world` unless
+    /// `stub_missing_functions` is set. See [`ImportAllowlist`] for why this
+    /// is validation-only and doesn't yet change the encoded component.
+    pub fn import_allowlist(mut self, resolve: &Resolve, world: WorldId) -> Self {
+        self.import_allowlist = Some(ImportAllowlist::from_world(resolve, world));
+        self
+    }
+}
+
+impl Linker {
+    /// Validates the linked libraries' imports against `allowlist`: `encode`
+    /// fails on an import outside the allowlist that no linked library
+    /// satisfies internally, unless `stub_missing_functions` is set. See
+    /// [`ImportAllowlist`] for why this is validation-only and doesn't yet
+    /// change the encoded component.
+    pub fn restrict_imports(mut self, allowlist: ImportAllowlist) -> Self {
+        self.import_allowlist = Some(allowlist);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn allowlist(names: &[&str]) -> ImportAllowlist {
+        ImportAllowlist {
+            names: names.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn no_allowlist_keeps_everything() {
+        let verdict = classify_import(None, false, "wasi:io/poll", false).unwrap();
+        assert_eq!(verdict, ImportDisposition::Keep);
+    }
+
+    #[test]
+    fn listed_import_is_kept() {
+        let allowlist = allowlist(&["wasi:io/poll"]);
+        let verdict = classify_import(Some(&allowlist), false, "wasi:io/poll", false).unwrap();
+        assert_eq!(verdict, ImportDisposition::Keep);
+    }
+
+    #[test]
+    fn unlisted_but_internally_satisfiable_import_is_internalized() {
+        let allowlist = allowlist(&["wasi:io/poll"]);
+        let verdict = classify_import(Some(&allowlist), false, "wasi:io/error", true).unwrap();
+        assert_eq!(verdict, ImportDisposition::Internalize);
+    }
+
+    #[test]
+    fn unlisted_unsatisfiable_import_is_stubbed_when_enabled() {
+        let allowlist = allowlist(&["wasi:io/poll"]);
+        let verdict = classify_import(Some(&allowlist), true, "wasi:io/error", false).unwrap();
+        assert_eq!(verdict, ImportDisposition::Stub);
+    }
+
+    #[test]
+    fn unlisted_unsatisfiable_import_errors_without_stubbing() {
+        let allowlist = allowlist(&["wasi:io/poll"]);
+        let err = classify_import(Some(&allowlist), false, "wasi:io/error", false).unwrap_err();
+        assert!(err.to_string().contains("wasi:io/error"));
+    }
+}
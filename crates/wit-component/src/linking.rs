@@ -0,0 +1,109 @@
+use crate::import_allowlist::{classify_import, ImportAllowlist};
+use crate::raw_component::{core_export_names, core_import_names, wrap_core_modules};
+use anyhow::{bail, Result};
+use std::collections::BTreeSet;
+
+/// Links together one or more core wasm libraries, plus optional adapters,
+/// into a single component.
+///
+/// Build up a linker with the builder methods below, then call
+/// [`Linker::encode`] to produce the linked component's bytes.
+pub struct Linker {
+    libraries: Vec<(String, Vec<u8>, bool)>,
+    adapters: Vec<(String, Vec<u8>)>,
+    validate: bool,
+    stub_missing_functions: bool,
+    pub(crate) import_allowlist: Option<ImportAllowlist>,
+}
+
+impl Default for Linker {
+    fn default() -> Self {
+        Linker {
+            libraries: Vec::new(),
+            adapters: Vec::new(),
+            validate: false,
+            stub_missing_functions: false,
+            import_allowlist: None,
+        }
+    }
+}
+
+impl Linker {
+    /// Configures whether each linked library and adapter is validated with
+    /// `wasmparser` before encoding.
+    pub fn validate(mut self, validate: bool) -> Self {
+        self.validate = validate;
+        self
+    }
+
+    /// Configures whether an import outside the configured allowlist (see
+    /// [`Linker::restrict_imports`]) that no linked library satisfies is
+    /// accepted rather than causing `encode` to fail. No stub is generated
+    /// for such an import; the encoded component is the same either way.
+    pub fn stub_missing_functions(mut self, stub: bool) -> Self {
+        self.stub_missing_functions = stub;
+        self
+    }
+
+    /// Registers a core wasm library, named `name`, to link in. `dl_openable`
+    /// marks whether this library may be `dlopen`-ed at runtime rather than
+    /// being statically linked in.
+    pub fn library(mut self, name: &str, wasm: &[u8], dl_openable: bool) -> Result<Self> {
+        self.libraries.push((name.to_string(), wasm.to_vec(), dl_openable));
+        Ok(self)
+    }
+
+    /// Registers an adapter module, named `name`, to be encoded alongside
+    /// the linked libraries.
+    pub fn adapter(mut self, name: &str, adapter: &[u8]) -> Result<Self> {
+        self.adapters.push((name.to_string(), adapter.to_vec()));
+        Ok(self)
+    }
+
+    /// Links the configured libraries (and adapters) and encodes the result
+    /// as a component.
+    pub fn encode(&self) -> Result<Vec<u8>> {
+        if self.validate {
+            for (_, wasm, _) in &self.libraries {
+                wasmparser::Validator::new().validate_all(wasm)?;
+            }
+            for (_, adapter) in &self.adapters {
+                wasmparser::Validator::new().validate_all(adapter)?;
+            }
+        }
+
+        // Everything one of the linked libraries exports is available to
+        // satisfy another library's import internally, without that import
+        // needing to show up on the composed component.
+        let exported: BTreeSet<String> = self
+            .libraries
+            .iter()
+            .flat_map(|(_, wasm, _)| core_export_names(wasm))
+            .collect();
+
+        for (_, wasm, _) in &self.libraries {
+            for import in core_import_names(wasm) {
+                // Imports are keyed `module:field`; exports by their name alone.
+                let satisfiable_internally = import
+                    .rsplit_once(':')
+                    .map_or(false, |(_, field)| exported.contains(field));
+                classify_import(
+                    self.import_allowlist.as_ref(),
+                    self.stub_missing_functions,
+                    &import,
+                    satisfiable_internally,
+                )?;
+            }
+        }
+
+        if self.libraries.is_empty() {
+            bail!("at least one library must be linked with `library` before calling `encode`");
+        }
+        let modules = self
+            .libraries
+            .iter()
+            .map(|(_, wasm, _)| wasm.as_slice())
+            .chain(self.adapters.iter().map(|(_, adapter)| adapter.as_slice()));
+        Ok(wrap_core_modules(modules))
+    }
+}
@@ -0,0 +1,66 @@
+//! Small helpers shared by [`crate::ComponentEncoder`] and [`crate::Linker`]
+//! for poking at core module contents and assembling the resulting
+//! component.
+
+use std::collections::BTreeSet;
+use wasm_encoder::{Component, RawSection};
+use wasmparser::{ExternalKind, Parser, Payload};
+
+/// The component-model binary section id for an embedded core module.
+const CORE_MODULE_SECTION_ID: u8 = 0x01;
+
+/// Collects `module:name` for every import in a core module's import
+/// section.
+pub(crate) fn core_import_names(module: &[u8]) -> BTreeSet<String> {
+    let mut names = BTreeSet::new();
+    for payload in Parser::new(0).parse_all(module) {
+        let payload = match payload {
+            Ok(payload) => payload,
+            Err(_) => break,
+        };
+        if let Payload::ImportSection(imports) = payload {
+            for import in imports {
+                let Ok(import) = import else { break };
+                names.insert(format!("{}:{}", import.module, import.name));
+            }
+        }
+    }
+    names
+}
+
+/// Collects the name of every function export in a core module's export
+/// section.
+pub(crate) fn core_export_names(module: &[u8]) -> BTreeSet<String> {
+    let mut names = BTreeSet::new();
+    for payload in Parser::new(0).parse_all(module) {
+        let payload = match payload {
+            Ok(payload) => payload,
+            Err(_) => break,
+        };
+        if let Payload::ExportSection(exports) = payload {
+            for export in exports {
+                let Ok(export) = export else { break };
+                if export.kind == ExternalKind::Func {
+                    names.insert(export.name.to_string());
+                }
+            }
+        }
+    }
+    names
+}
+
+/// Wraps every core module in `modules`, in order, as a single component
+/// that embeds each of them as its own core module section.
+///
+/// Callers are responsible for passing every module that needs to end up in
+/// the output - e.g. all of a `Linker`'s libraries, not just the first one.
+pub(crate) fn wrap_core_modules<'a>(modules: impl IntoIterator<Item = &'a [u8]>) -> Vec<u8> {
+    let mut component = Component::new();
+    for module in modules {
+        component.section(&RawSection {
+            id: CORE_MODULE_SECTION_ID,
+            data: module,
+        });
+    }
+    component.finish()
+}
@@ -0,0 +1,276 @@
+use crate::ComponentEncoder;
+use anyhow::{bail, Result};
+use semver::Version;
+use wasmparser::{Parser, Payload};
+
+/// The clang version below which `cabi_realloc` is known to be miscompiled
+/// by the wasi-libc allocator, unless a caller configures a different
+/// threshold via [`ComponentEncoder::realloc_safety_threshold`].
+const DEFAULT_REALLOC_SAFETY_THRESHOLD: (u64, u64, u64) = (15, 0, 7);
+
+/// The result of checking a core module for the wasi-libc `cabi_realloc`
+/// miscompilation.
+///
+/// See [`ComponentEncoder::reject_unsafe_realloc`] for how this is acted on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReallocSafety {
+    /// The module exports `cabi_realloc` and was built with a `clang`
+    /// whose version is at or above the safety threshold.
+    ProbablySafe,
+    /// The module exports `cabi_realloc` and was built with a `clang`
+    /// whose version is below the safety threshold.
+    ProbablyUnsafe {
+        /// The clang version parsed from the module's producers section.
+        version: Version,
+    },
+    /// Either the module doesn't export `cabi_realloc`, or no parseable
+    /// `clang`/`clang_version` entry was found in its producers section.
+    Unknown,
+}
+
+impl ComponentEncoder {
+    /// Configures whether a module classified as [`ReallocSafety::ProbablyUnsafe`]
+    /// causes [`ComponentEncoder::encode`] to return an error.
+    ///
+    /// By default this is `false` and a warning is logged instead via the
+    /// `log` crate.
+    pub fn reject_unsafe_realloc(mut self, reject: bool) -> Self {
+        self.reject_unsafe_realloc = reject;
+        self
+    }
+
+    /// Overrides the `clang` version, defaulting to `15.0.7`, at or above
+    /// which a module exporting `cabi_realloc` is considered
+    /// [`ReallocSafety::ProbablySafe`].
+    pub fn realloc_safety_threshold(mut self, threshold: Version) -> Self {
+        self.realloc_safety_threshold = threshold;
+        self
+    }
+
+    pub(crate) fn default_realloc_safety_threshold() -> Version {
+        let (major, minor, patch) = DEFAULT_REALLOC_SAFETY_THRESHOLD;
+        Version::new(major, minor, patch)
+    }
+
+    /// Checks `module` for the wasi-libc `cabi_realloc` miscompilation and,
+    /// depending on [`ComponentEncoder::reject_unsafe_realloc`], either
+    /// errors out or logs a warning when the module is classified as
+    /// [`ReallocSafety::ProbablyUnsafe`].
+    pub(crate) fn check_realloc_safety(&self, name: &str, module: &[u8]) -> Result<()> {
+        match classify_realloc_safety(module, &self.realloc_safety_threshold) {
+            ReallocSafety::ProbablyUnsafe { version } => {
+                let msg = format!(
+                    "module `{name}` exports `cabi_realloc` but was built with clang \
+                     {version}, which is known to miscompile the canonical ABI realloc path \
+                     (fixed in {}); rebuild with a newer toolchain",
+                    self.realloc_safety_threshold,
+                );
+                if self.reject_unsafe_realloc {
+                    bail!(msg);
+                }
+                log::warn!("{msg}");
+                Ok(())
+            }
+            ReallocSafety::ProbablySafe | ReallocSafety::Unknown => Ok(()),
+        }
+    }
+}
+
+/// Classifies a core module's use of `cabi_realloc` against `threshold`.
+fn classify_realloc_safety(module: &[u8], threshold: &Version) -> ReallocSafety {
+    if !exports_cabi_realloc(module) {
+        return ReallocSafety::Unknown;
+    }
+
+    match clang_version(module) {
+        Some(version) if version >= *threshold => ReallocSafety::ProbablySafe,
+        Some(version) => ReallocSafety::ProbablyUnsafe { version },
+        None => ReallocSafety::Unknown,
+    }
+}
+
+fn exports_cabi_realloc(module: &[u8]) -> bool {
+    for payload in Parser::new(0).parse_all(module) {
+        let payload = match payload {
+            Ok(payload) => payload,
+            Err(_) => return false,
+        };
+        if let Payload::ExportSection(exports) = payload {
+            for export in exports {
+                let export = match export {
+                    Ok(export) => export,
+                    Err(_) => return false,
+                };
+                if export.kind == wasmparser::ExternalKind::Func && export.name == "cabi_realloc"
+                {
+                    return true;
+                }
+            }
+        }
+    }
+    false
+}
+
+/// Looks for a `clang`/`clang_version` entry in the module's producers
+/// custom section and, if found, parses it as a semver version.
+fn clang_version(module: &[u8]) -> Option<Version> {
+    let metadata = wasm_metadata::Metadata::from_binary(module).ok()?;
+    let producers = match metadata {
+        wasm_metadata::Metadata::Module { producers, .. } => producers,
+        _ => None,
+    }?;
+    let field = producers
+        .get("language")
+        .and_then(|f| f.get("clang"))
+        .or_else(|| producers.get("processed-by").and_then(|f| f.get("clang")))
+        .or_else(|| {
+            producers
+                .get("processed-by")
+                .and_then(|f| f.get("clang_version"))
+        })?;
+    parse_semver_prefix(field)
+}
+
+/// Parses the leading `MAJOR.MINOR.PATCH` out of a free-form version string
+/// like `"15.0.7"` or `"15.0.7 (https://github.com/llvm/llvm-project ...)"`.
+fn parse_semver_prefix(s: &str) -> Option<Version> {
+    let digits_and_dots: String = s
+        .chars()
+        .take_while(|c| c.is_ascii_digit() || *c == '.')
+        .collect();
+    Version::parse(&digits_and_dots).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::borrow::Cow;
+    use wasm_encoder::{
+        CodeSection, CustomSection, Encode, ExportKind, ExportSection, Function, FunctionSection,
+        Instruction, Module, Section, TypeSection,
+    };
+
+    fn leb_u32(mut n: u32) -> Vec<u8> {
+        let mut out = Vec::new();
+        loop {
+            let byte = (n & 0x7f) as u8;
+            n >>= 7;
+            if n == 0 {
+                out.push(byte);
+                break;
+            }
+            out.push(byte | 0x80);
+        }
+        out
+    }
+
+    fn wasm_name(s: &str) -> Vec<u8> {
+        let mut out = leb_u32(s.len() as u32);
+        out.extend_from_slice(s.as_bytes());
+        out
+    }
+
+    /// A `producers` custom section with a single `processed-by` entry
+    /// naming `clang` at `version`, per the tool-conventions producers
+    /// section layout that `wasm_metadata` parses.
+    fn producers_section(version: &str) -> Vec<u8> {
+        let mut data = leb_u32(1); // one field
+        data.extend(wasm_name("processed-by"));
+        data.extend(leb_u32(1)); // one value for that field
+        data.extend(wasm_name("clang"));
+        data.extend(wasm_name(version));
+
+        let section = CustomSection {
+            name: "producers".into(),
+            data: Cow::Owned(data),
+        };
+        let mut out = Vec::new();
+        out.push(section.id());
+        section.encode(&mut out);
+        out
+    }
+
+    /// A minimal module, optionally exporting `cabi_realloc` and/or
+    /// carrying a `producers` section claiming the given clang version.
+    fn build_module(export_realloc: bool, clang_version: Option<&str>) -> Vec<u8> {
+        let mut module = Module::new();
+
+        let mut types = TypeSection::new();
+        types.function([], []);
+        module.section(&types);
+
+        let mut functions = FunctionSection::new();
+        functions.function(0);
+        module.section(&functions);
+
+        if export_realloc {
+            let mut exports = ExportSection::new();
+            exports.export("cabi_realloc", ExportKind::Func, 0);
+            module.section(&exports);
+        }
+
+        let mut code = CodeSection::new();
+        let mut f = Function::new([]);
+        f.instruction(&Instruction::End);
+        code.function(&f);
+        module.section(&code);
+
+        let mut bytes = module.finish();
+        if let Some(version) = clang_version {
+            bytes.extend(producers_section(version));
+        }
+        bytes
+    }
+
+    #[test]
+    fn unknown_without_cabi_realloc_export() {
+        let module = build_module(false, Some("15.0.7"));
+        assert_eq!(
+            classify_realloc_safety(&module, &Version::new(15, 0, 7)),
+            ReallocSafety::Unknown
+        );
+    }
+
+    #[test]
+    fn unknown_without_clang_version() {
+        let module = build_module(true, None);
+        assert_eq!(
+            classify_realloc_safety(&module, &Version::new(15, 0, 7)),
+            ReallocSafety::Unknown
+        );
+    }
+
+    #[test]
+    fn probably_unsafe_below_threshold() {
+        let module = build_module(true, Some("14.0.0"));
+        assert_eq!(
+            classify_realloc_safety(&module, &Version::new(15, 0, 7)),
+            ReallocSafety::ProbablyUnsafe {
+                version: Version::new(14, 0, 0)
+            }
+        );
+    }
+
+    #[test]
+    fn probably_safe_at_or_above_threshold() {
+        let module = build_module(true, Some("15.0.7"));
+        assert_eq!(
+            classify_realloc_safety(&module, &Version::new(15, 0, 7)),
+            ReallocSafety::ProbablySafe
+        );
+    }
+
+    #[test]
+    fn rejects_unsafe_module_when_configured() {
+        let module = build_module(true, Some("14.0.0"));
+        let encoder = ComponentEncoder::default().reject_unsafe_realloc(true);
+        assert!(encoder.check_realloc_safety("test", &module).is_err());
+    }
+
+    #[test]
+    fn warns_instead_of_rejecting_by_default() {
+        let module = build_module(true, Some("14.0.0"));
+        let encoder = ComponentEncoder::default();
+        assert!(encoder.check_realloc_safety("test", &module).is_ok());
+    }
+}
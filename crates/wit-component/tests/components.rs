@@ -2,7 +2,9 @@ use anyhow::{bail, Context, Error, Result};
 use pretty_assertions::assert_eq;
 use std::{borrow::Cow, fs, path::Path};
 use wasm_encoder::{Encode, Section};
-use wit_component::{ComponentEncoder, DecodedWasm, Linker, StringEncoding, WitPrinter};
+use wit_component::{
+    ComponentEncoder, DecodedWasm, ImportAllowlist, Linker, StringEncoding, WitPrinter,
+};
 use wit_parser::{PackageId, Resolve, UnresolvedPackage};
 
 /// Tests the encoding of components.
@@ -64,14 +66,18 @@ fn component_encoding_via_flags() -> Result<()> {
         let mut adapters = glob::glob(path.join("adapt-*.wat").to_str().unwrap())?;
         let result = if module_path.is_file() {
             let module = read_core_module(&module_path, &resolve, pkg)?;
+            let mut encoder = ComponentEncoder::default().module(&module)?.validate(true);
+            if path.join("reject-unsafe-realloc").is_file() {
+                encoder = encoder.reject_unsafe_realloc(true);
+            }
+            if let Some(allowlist) = read_import_allowlist(&path)? {
+                encoder = encoder.with_import_allowlist(allowlist);
+            }
             adapters
-                .try_fold(
-                    ComponentEncoder::default().module(&module)?.validate(true),
-                    |encoder, path| {
-                        let (name, wasm) = read_name_and_module("adapt-", &path?, &resolve, pkg)?;
-                        Ok::<_, Error>(encoder.adapter(&name, &wasm)?)
-                    },
-                )?
+                .try_fold(encoder, |encoder, path| {
+                    let (name, wasm) = read_name_and_module("adapt-", &path?, &resolve, pkg)?;
+                    Ok::<_, Error>(encoder.adapter(&name, &wasm)?)
+                })?
                 .encode()
         } else {
             let mut libs = glob::glob(path.join("lib-*.wat").to_str().unwrap())?
@@ -86,6 +92,9 @@ fn component_encoding_via_flags() -> Result<()> {
             if path.join("stub-missing-functions").is_file() {
                 linker = linker.stub_missing_functions(true);
             }
+            if let Some(allowlist) = read_import_allowlist(&path)? {
+                linker = linker.restrict_imports(allowlist);
+            }
 
             let linker = libs.try_fold(linker, |linker, (prefix, path, dl_openable)| {
                 let (name, wasm) = read_name_and_module(prefix, &path?, &resolve, pkg)?;
@@ -170,6 +179,20 @@ fn component_encoding_via_flags() -> Result<()> {
     Ok(())
 }
 
+/// Reads the test case's optional `import-allowlist` file, one import name
+/// per non-empty line, into an [`ImportAllowlist`].
+fn read_import_allowlist(path: &Path) -> Result<Option<ImportAllowlist>> {
+    let allowlist_path = path.join("import-allowlist");
+    if !allowlist_path.is_file() {
+        return Ok(None);
+    }
+    let contents = fs::read_to_string(&allowlist_path)?;
+    let names = contents.lines().map(str::trim).filter(|l| !l.is_empty());
+    Ok(Some(ImportAllowlist::from_names(
+        names.map(|n| n.to_owned()),
+    )))
+}
+
 fn read_name_and_module(
     prefix: &str,
     path: &Path,
@@ -0,0 +1,209 @@
+use crate::{PackageId, PackageName, Resolve};
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// A content-addressed record of the exact WIT packages that went into
+/// resolving a [`Resolve`].
+///
+/// A lockfile pins each package to a `(version, digest)` pair, where the
+/// digest is computed over the canonical encoded bytes of that package (the
+/// same bytes that `wit_component::encode` would produce for it). Build
+/// tools can generate one with [`Resolve::lock`] and later check a resolve
+/// against it with [`Resolve::verify_lock`] to detect a transitively
+/// resolved dependency changing out from under them.
+#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct LockFile {
+    /// Locked packages, keyed by package name and sorted for stable output.
+    package: BTreeMap<String, LockedPackage>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+struct LockedPackage {
+    version: Option<String>,
+    digest: String,
+}
+
+impl LockFile {
+    /// Parses a lockfile from its TOML representation.
+    pub fn parse(contents: &str) -> Result<LockFile> {
+        Ok(toml::from_str(contents)?)
+    }
+
+    /// Serializes this lockfile to its TOML representation.
+    pub fn serialize(&self) -> Result<String> {
+        Ok(toml::to_string_pretty(self)?)
+    }
+}
+
+impl fmt::Display for LockFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.serialize() {
+            Ok(s) => f.write_str(&s),
+            Err(_) => Err(fmt::Error),
+        }
+    }
+}
+
+impl Resolve {
+    /// Generates a [`LockFile`] pinning every package currently registered
+    /// in this `Resolve` to its semver version and a SHA-256 digest of its
+    /// canonical encoded bytes.
+    ///
+    /// `encode` is called once per package and should produce the same
+    /// bytes that will later be embedded in a component for that package,
+    /// typically by delegating to `wit_component::encode(None, self, id)`.
+    /// It's threaded through as a callback rather than called directly to
+    /// avoid a dependency from this crate onto `wit-component`.
+    pub fn lock(
+        &self,
+        mut encode: impl FnMut(&Resolve, PackageId) -> Result<Vec<u8>>,
+    ) -> Result<LockFile> {
+        let mut lock = LockFile::default();
+        for (id, pkg) in self.packages.iter() {
+            let bytes = encode(self, id)?;
+            let digest = hex_digest(&bytes);
+            lock.package.insert(
+                package_key(&pkg.name),
+                LockedPackage {
+                    version: pkg.name.version.as_ref().map(|v| v.to_string()),
+                    digest,
+                },
+            );
+        }
+        Ok(lock)
+    }
+
+    /// Verifies that every package in this `Resolve` matches the version
+    /// and digest recorded in `lock`, erroring if a package is missing,
+    /// extra, or has drifted from what's recorded.
+    pub fn verify_lock(
+        &self,
+        lock: &LockFile,
+        mut encode: impl FnMut(&Resolve, PackageId) -> Result<Vec<u8>>,
+    ) -> Result<()> {
+        let mut remaining: BTreeMap<&String, &LockedPackage> = lock.package.iter().collect();
+
+        for (id, pkg) in self.packages.iter() {
+            let name = pkg.name.to_string();
+            let key = package_key(&pkg.name);
+            let locked = match remaining.remove(&key) {
+                Some(locked) => locked,
+                None => bail!("package `{name}` is not present in the lockfile"),
+            };
+
+            let version = pkg.name.version.as_ref().map(|v| v.to_string());
+            if version != locked.version {
+                bail!(
+                    "package `{name}` version mismatch: lockfile has {:?}, resolve has {:?}",
+                    locked.version,
+                    version,
+                );
+            }
+
+            let bytes = encode(self, id)?;
+            let digest = hex_digest(&bytes);
+            if digest != locked.digest {
+                bail!(
+                    "package `{name}` digest mismatch: the resolved package's contents no \
+                     longer match what's recorded in the lockfile; re-lock if this change was \
+                     intentional"
+                );
+            }
+        }
+
+        if let Some((name, _)) = remaining.into_iter().next() {
+            bail!("lockfile references package `{name}` which is no longer in the resolve");
+        }
+
+        Ok(())
+    }
+}
+
+fn hex_digest(bytes: &[u8]) -> String {
+    let digest = Sha256::digest(bytes);
+    let mut s = String::with_capacity(digest.len() * 2);
+    for byte in digest {
+        s.push_str(&format!("{byte:02x}"));
+    }
+    s
+}
+
+/// The lockfile map key for a package: its namespace and name, *without* the
+/// version. `PackageName`'s `Display` impl includes `@version`, so keying on
+/// that directly would make a version bump look like an entirely different
+/// package (landing on the "not present"/"no longer in the resolve" branches)
+/// instead of tripping the dedicated version-mismatch check in
+/// [`Resolve::verify_lock`].
+fn package_key(name: &PackageName) -> String {
+    format!("{}:{}", name.namespace, name.name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::UnresolvedPackage;
+    use std::path::Path;
+
+    fn resolve_with_one_package() -> (Resolve, PackageId) {
+        let mut resolve = Resolve::default();
+        let unresolved = UnresolvedPackage::parse(
+            Path::new("test.wit"),
+            "package test:pkg@1.0.0;\n\nworld w {}\n",
+        )
+        .unwrap();
+        let pkg = resolve.push(unresolved).unwrap();
+        (resolve, pkg)
+    }
+
+    fn fake_encode(_resolve: &Resolve, _pkg: PackageId) -> Result<Vec<u8>> {
+        Ok(b"fake-encoded-bytes".to_vec())
+    }
+
+    #[test]
+    fn round_trips_through_lock_and_verify() {
+        let (resolve, _pkg) = resolve_with_one_package();
+        let lock = resolve.lock(fake_encode).unwrap();
+        resolve.verify_lock(&lock, fake_encode).unwrap();
+    }
+
+    #[test]
+    fn detects_digest_mismatch() {
+        let (resolve, _pkg) = resolve_with_one_package();
+        let lock = resolve.lock(fake_encode).unwrap();
+        let err = resolve
+            .verify_lock(&lock, |_, _| Ok(b"different-bytes".to_vec()))
+            .unwrap_err();
+        assert!(err.to_string().contains("digest mismatch"));
+    }
+
+    #[test]
+    fn detects_missing_package() {
+        let (resolve, _pkg) = resolve_with_one_package();
+        let empty = LockFile::default();
+        let err = resolve.verify_lock(&empty, fake_encode).unwrap_err();
+        assert!(err.to_string().contains("not present in the lockfile"));
+    }
+
+    #[test]
+    fn detects_extra_package() {
+        let (resolve, _pkg) = resolve_with_one_package();
+        let lock = resolve.lock(fake_encode).unwrap();
+        let empty_resolve = Resolve::default();
+        let err = empty_resolve.verify_lock(&lock, fake_encode).unwrap_err();
+        assert!(err.to_string().contains("no longer in the resolve"));
+    }
+
+    #[test]
+    fn detects_version_mismatch_without_changing_the_lock_key() {
+        let (resolve, _pkg) = resolve_with_one_package();
+        let mut lock = resolve.lock(fake_encode).unwrap();
+        for locked in lock.package.values_mut() {
+            locked.version = Some("2.0.0".to_string());
+        }
+        let err = resolve.verify_lock(&lock, fake_encode).unwrap_err();
+        assert!(err.to_string().contains("version mismatch"));
+    }
+}
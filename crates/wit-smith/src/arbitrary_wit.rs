@@ -0,0 +1,237 @@
+use crate::Config;
+use arbitrary::{Arbitrary, Result, Unstructured};
+use wit_component::DecodedWasm;
+use wit_parser::{InterfaceId, PackageId, Resolve, TypeId, WorldId};
+
+/// A generated WIT package exposed as a structured `Arbitrary` value.
+///
+/// Generating straight to bytes (as `wit_smith::smith` does) works for
+/// libFuzzer but means a minimizer can only shrink the raw byte buffer, not
+/// the WIT document it represents. `ArbitraryWit` instead carries the
+/// decoded [`Resolve`] and [`PackageId`] alongside the encoded bytes, and
+/// [`ArbitraryWit::shrink_once`] shrinks by dropping whole worlds,
+/// interfaces, and type definitions and re-encoding, so a failing input can
+/// be minimized down to a small, human-readable WIT package.
+///
+/// The pinned `arbitrary` crate's `Arbitrary` trait (1.x) has no `shrink`
+/// method to hook into - that was removed after 0.4 - so this can't plug
+/// into `cargo fuzz tmin`'s byte-level minimizer directly. Call
+/// `shrink_once` in a loop from a standalone reducer instead: keep the
+/// smallest candidate that still reproduces the failure, and stop once none
+/// do.
+pub struct ArbitraryWit {
+    /// The resolve containing the generated package (and anything it
+    /// depends on).
+    pub resolve: Resolve,
+    /// The package within `resolve` that was generated.
+    pub package: PackageId,
+    /// The encoded bytes of `package`, as produced by `wit_component::encode`.
+    pub wasm: Vec<u8>,
+}
+
+impl ArbitraryWit {
+    /// Re-encodes `resolve`/`package` after a shrink step mutated them.
+    ///
+    /// Unlike the initial generation in `arbitrary`, a shrunk candidate that
+    /// fails to re-encode isn't a bug worth crashing on - the edit (e.g.
+    /// dropping a world a type still refers to) may simply have produced an
+    /// invalid document, so the candidate is just dropped from the shrink
+    /// sequence instead.
+    fn from_resolve(resolve: Resolve, package: PackageId) -> Option<ArbitraryWit> {
+        let wasm = wit_component::encode(None, &resolve, package).ok()?;
+        Some(ArbitraryWit {
+            resolve,
+            package,
+            wasm,
+        })
+    }
+
+    /// Interfaces defined directly in the generated package, available as
+    /// shrink targets.
+    fn interfaces(&self) -> Vec<InterfaceId> {
+        self.resolve.packages[self.package]
+            .interfaces
+            .values()
+            .copied()
+            .collect()
+    }
+
+    /// Named types defined directly in one of the package's interfaces,
+    /// available as shrink targets, alongside the interface that owns them.
+    fn interface_types(&self) -> Vec<(InterfaceId, TypeId)> {
+        self.interfaces()
+            .into_iter()
+            .flat_map(|interface| {
+                self.resolve.interfaces[interface]
+                    .types
+                    .values()
+                    .copied()
+                    .map(move |ty| (interface, ty))
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    /// Worlds defined directly in the generated package, available as
+    /// shrink targets.
+    fn worlds(&self) -> Vec<WorldId> {
+        self.resolve.packages[self.package]
+            .worlds
+            .values()
+            .copied()
+            .collect()
+    }
+
+    /// Returns a copy of this value with `world` dropped from the package's
+    /// world list, or `None` if that fails to re-encode (e.g. another world
+    /// or interface still refers to it).
+    ///
+    /// This only unlists `world` from the package; the underlying arena
+    /// entry is left in place (arenas are append-only) but becomes
+    /// unreachable from the package, which is all `encode` looks at.
+    fn without_world(&self, world: WorldId) -> Option<ArbitraryWit> {
+        let mut resolve = self.resolve.clone();
+        let pkg = &mut resolve.packages[self.package];
+        let name = pkg
+            .worlds
+            .iter()
+            .find(|(_, id)| **id == world)
+            .map(|(name, _)| name.clone())?;
+        pkg.worlds.remove(&name);
+        ArbitraryWit::from_resolve(resolve, self.package)
+    }
+
+    /// Returns a copy of this value with `interface` dropped from the
+    /// package's interface list, or `None` if that fails to re-encode (e.g.
+    /// a world still imports or exports it).
+    fn without_interface(&self, interface: InterfaceId) -> Option<ArbitraryWit> {
+        let mut resolve = self.resolve.clone();
+        let pkg = &mut resolve.packages[self.package];
+        let name = pkg
+            .interfaces
+            .iter()
+            .find(|(_, id)| **id == interface)
+            .map(|(name, _)| name.clone())?;
+        pkg.interfaces.remove(&name);
+        ArbitraryWit::from_resolve(resolve, self.package)
+    }
+
+    /// Returns a copy of this value with the named type `ty` dropped from
+    /// `interface`, or `None` if that fails to re-encode (e.g. a function in
+    /// the same or another interface still refers to it).
+    fn without_type(&self, interface: InterfaceId, ty: TypeId) -> Option<ArbitraryWit> {
+        let mut resolve = self.resolve.clone();
+        let iface = &mut resolve.interfaces[interface];
+        let name = iface
+            .types
+            .iter()
+            .find(|(_, id)| **id == ty)
+            .map(|(name, _)| name.clone())?;
+        iface.types.remove(&name);
+        ArbitraryWit::from_resolve(resolve, self.package)
+    }
+
+    /// Returns every way this value can be shrunk by one step: dropping a
+    /// single world, interface, or named type and re-encoding. Candidates
+    /// that fail to re-encode (e.g. removing something still referenced
+    /// elsewhere) are silently omitted.
+    pub fn shrink_once(&self) -> Vec<ArbitraryWit> {
+        let worlds = self.worlds().into_iter().filter_map(|world| self.without_world(world));
+        let interfaces = self
+            .interfaces()
+            .into_iter()
+            .filter_map(|interface| self.without_interface(interface));
+        let types = self
+            .interface_types()
+            .into_iter()
+            .filter_map(|(interface, ty)| self.without_type(interface, ty));
+        worlds.chain(interfaces).chain(types).collect()
+    }
+}
+
+impl<'a> Arbitrary<'a> for ArbitraryWit {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let config: Config = u.arbitrary()?;
+        let wasm = crate::smith(&config, u)?;
+        // A document fresh out of `smith` is expected to always decode; if
+        // it doesn't, that's a `wit_smith` bug worth crashing the fuzzer
+        // over, same as the `.unwrap()` this replaces used to.
+        let (resolve, package) = match wit_component::decode(&wasm).unwrap() {
+            DecodedWasm::WitPackage(resolve, package) => (resolve, package),
+            DecodedWasm::Component(..) => {
+                unreachable!("wit_smith only ever generates standalone WIT packages")
+            }
+        };
+        Ok(ArbitraryWit {
+            resolve,
+            package,
+            wasm,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+    use wit_parser::UnresolvedPackage;
+
+    /// A package with an unreferenced named type, an interface, and a world,
+    /// so all three `shrink_once` steps have something to drop.
+    fn non_trivial_wit() -> ArbitraryWit {
+        let mut resolve = Resolve::default();
+        let unresolved = UnresolvedPackage::parse(
+            Path::new("test.wit"),
+            "package test:pkg@1.0.0;\n\
+             \n\
+             interface logging {\n\
+             \u{20}   record level { value: u32 }\n\
+             \u{20}   log: func(msg: string);\n\
+             }\n\
+             \n\
+             world w {\n\
+             \u{20}   import logging;\n\
+             \u{20}   export run: func();\n\
+             }\n",
+        )
+        .unwrap();
+        let package = resolve.push(unresolved).unwrap();
+        ArbitraryWit::from_resolve(resolve, package).expect("well-formed package encodes")
+    }
+
+    #[test]
+    fn shrink_once_produces_smaller_candidates() {
+        let wit = non_trivial_wit();
+        let candidates = wit.shrink_once();
+        assert!(
+            !candidates.is_empty(),
+            "expected at least one shrink candidate"
+        );
+
+        let size = |w: &ArbitraryWit| {
+            w.worlds().len() + w.interfaces().len() + w.interface_types().len()
+        };
+        for candidate in &candidates {
+            assert!(size(candidate) < size(&wit), "candidate is not smaller");
+
+            // Every candidate `from_resolve` returned already re-encoded
+            // successfully; check it also decodes back, i.e. it's a valid
+            // standalone WIT package and not just bytes that happened to be
+            // produced.
+            let decoded = wit_component::decode(&candidate.wasm).expect("candidate decodes");
+            match decoded {
+                DecodedWasm::WitPackage(..) => {}
+                DecodedWasm::Component(..) => panic!("expected a WIT package, not a component"),
+            }
+        }
+    }
+
+    #[test]
+    fn without_type_drops_an_unreferenced_record() {
+        let wit = non_trivial_wit();
+        let interface = wit.interfaces()[0];
+        let ty = wit.interface_types()[0].1;
+        let shrunk = wit.without_type(interface, ty).expect("record is unreferenced");
+        assert!(shrunk.resolve.interfaces[interface].types.is_empty());
+    }
+}
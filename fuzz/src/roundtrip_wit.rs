@@ -3,18 +3,22 @@ use std::borrow::Cow;
 use std::path::Path;
 use wasm_encoder::{CustomSection, Encode, Section};
 use wit_component::*;
-use wit_parser::{Resolve, SourceMap};
+use wit_parser::SourceMap;
+use wit_smith::ArbitraryWit;
 
 pub fn run(u: &mut Unstructured<'_>) -> Result<()> {
-    let wasm = u.arbitrary().and_then(|config| {
-        log::debug!("config: {config:#?}");
-        wit_smith::smith(&config, u)
-    })?;
+    // Generating `ArbitraryWit` directly, instead of raw bytes via
+    // `wit_smith::smith`, hands over the decoded `Resolve` alongside the
+    // bytes. libFuzzer's own minimizer still shrinks only the byte buffer; a
+    // failing input can be reduced by structure (worlds, interfaces, types)
+    // with `ArbitraryWit::shrink_once`.
+    let wit: ArbitraryWit = u.arbitrary()?;
+    let ArbitraryWit {
+        resolve,
+        package: pkg,
+        wasm,
+    } = wit;
     write_file("doc1.wasm", &wasm);
-    let (resolve, pkg) = match wit_component::decode(&wasm).unwrap() {
-        DecodedWasm::WitPackage(resolve, pkg) => (resolve, pkg),
-        DecodedWasm::Component(..) => unreachable!(),
-    };
 
     // If we've decoded an empty package, make sure to only use the v1 decoder (v2 has no way to
     // represent an empty document).